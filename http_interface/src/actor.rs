@@ -0,0 +1,361 @@
+//! Per-owner actor supervisor for product storage. Each owner's commands run
+//! on a single dedicated task, so a read-then-write like `modify` is
+//! serialized against that owner's other operations, while different owners
+//! still proceed fully in parallel. Idle actors shut themselves down after
+//! [`IDLE_TIMEOUT`]; the supervisor respawns one lazily the next time that
+//! owner is addressed.
+
+use std::{collections::HashMap, sync::Arc, sync::Mutex, time::Duration};
+
+use tokio::sync::{mpsc, oneshot};
+use uuid::Uuid;
+
+use crate::{
+    repository::{ProductRepository, RepositoryError},
+    Product,
+};
+
+const MAILBOX_CAPACITY: usize = 32;
+// shortened under test so the idle-shutdown/respawn path doesn't need a
+// multi-minute sleep to exercise
+#[cfg(not(test))]
+const IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+#[cfg(test)]
+const IDLE_TIMEOUT: Duration = Duration::from_millis(50);
+
+type Reply<T> = oneshot::Sender<Result<T, RepositoryError>>;
+
+enum Command {
+    Create {
+        name: String,
+        description: String,
+        reply: Reply<Product>,
+    },
+    List {
+        limit: i64,
+        offset: i64,
+        reply: Reply<Vec<Product>>,
+    },
+    Read {
+        id: Uuid,
+        reply: Reply<Product>,
+    },
+    Modify {
+        id: Uuid,
+        name: Option<String>,
+        description: Option<String>,
+        reply: Reply<()>,
+    },
+    Delete {
+        id: Uuid,
+        reply: Reply<()>,
+    },
+    SetImage {
+        id: Uuid,
+        image_url: String,
+        reply: Reply<()>,
+    },
+}
+
+pub struct ProductActorSupervisor {
+    repo: Arc<dyn ProductRepository>,
+    actors: Mutex<HashMap<Uuid, mpsc::Sender<Command>>>,
+}
+
+impl ProductActorSupervisor {
+    pub fn new(repo: Arc<dyn ProductRepository>) -> Self {
+        Self {
+            repo,
+            actors: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn create(&self, owner: Uuid, name: String, description: String) -> Result<Product, RepositoryError> {
+        let (reply, rx) = oneshot::channel();
+        self.dispatch(owner, Command::Create { name, description, reply }).await;
+        await_reply(rx).await
+    }
+
+    pub async fn list(&self, owner: Uuid, limit: i64, offset: i64) -> Result<Vec<Product>, RepositoryError> {
+        let (reply, rx) = oneshot::channel();
+        self.dispatch(owner, Command::List { limit, offset, reply }).await;
+        await_reply(rx).await
+    }
+
+    pub async fn read(&self, owner: Uuid, id: Uuid) -> Result<Product, RepositoryError> {
+        let (reply, rx) = oneshot::channel();
+        self.dispatch(owner, Command::Read { id, reply }).await;
+        await_reply(rx).await
+    }
+
+    pub async fn modify(
+        &self,
+        owner: Uuid,
+        id: Uuid,
+        name: Option<String>,
+        description: Option<String>,
+    ) -> Result<(), RepositoryError> {
+        let (reply, rx) = oneshot::channel();
+        self.dispatch(owner, Command::Modify { id, name, description, reply }).await;
+        await_reply(rx).await
+    }
+
+    pub async fn delete(&self, owner: Uuid, id: Uuid) -> Result<(), RepositoryError> {
+        let (reply, rx) = oneshot::channel();
+        self.dispatch(owner, Command::Delete { id, reply }).await;
+        await_reply(rx).await
+    }
+
+    pub async fn set_image(&self, owner: Uuid, id: Uuid, image_url: String) -> Result<(), RepositoryError> {
+        let (reply, rx) = oneshot::channel();
+        self.dispatch(owner, Command::SetImage { id, image_url, reply }).await;
+        await_reply(rx).await
+    }
+
+    /// Sends `command` to `owner`'s actor, spawning one if it doesn't exist
+    /// or has shut itself down after sitting idle. Retries once against a
+    /// freshly spawned actor if the old mailbox turns out to be closed.
+    async fn dispatch(&self, owner: Uuid, command: Command) {
+        let mut command = command;
+        for _ in 0..2 {
+            let sender = self.sender_for(owner);
+            match sender.send(command).await {
+                Ok(()) => return,
+                Err(mpsc::error::SendError(returned)) => {
+                    self.actors.lock().unwrap().remove(&owner);
+                    command = returned;
+                }
+            }
+        }
+    }
+
+    fn sender_for(&self, owner: Uuid) -> mpsc::Sender<Command> {
+        let mut actors = self.actors.lock().unwrap();
+        if let Some(sender) = actors.get(&owner) {
+            if !sender.is_closed() {
+                return sender.clone();
+            }
+        }
+
+        let (sender, rx) = mpsc::channel(MAILBOX_CAPACITY);
+        tokio::spawn(run_actor(owner, self.repo.clone(), rx));
+        actors.insert(owner, sender.clone());
+        sender
+    }
+}
+
+async fn run_actor(owner: Uuid, repo: Arc<dyn ProductRepository>, mut rx: mpsc::Receiver<Command>) {
+    loop {
+        let command = match tokio::time::timeout(IDLE_TIMEOUT, rx.recv()).await {
+            Ok(Some(command)) => command,
+            Ok(None) => break,
+            Err(_elapsed) => {
+                tracing::debug!("product actor for {owner} shutting down after idle timeout");
+                break;
+            }
+        };
+
+        match command {
+            Command::Create { name, description, reply } => {
+                let result = repo.create(owner, &name, &description).await;
+                let _ = reply.send(result);
+            }
+            Command::List { limit, offset, reply } => {
+                let result = repo.list(owner, limit, offset).await;
+                let _ = reply.send(result);
+            }
+            Command::Read { id, reply } => {
+                let result = repo.read(id, owner).await;
+                let _ = reply.send(result);
+            }
+            Command::Modify { id, name, description, reply } => {
+                let result = modify(repo.as_ref(), owner, id, name, description).await;
+                let _ = reply.send(result);
+            }
+            Command::Delete { id, reply } => {
+                let result = repo.delete(id, owner).await;
+                let _ = reply.send(result);
+            }
+            Command::SetImage { id, image_url, reply } => {
+                let result = repo.set_image(id, owner, &image_url).await;
+                let _ = reply.send(result);
+            }
+        }
+    }
+}
+
+/// Runs read-then-write as one step on the owner's actor, so concurrent
+/// modifications to the same product can't interleave.
+async fn modify(
+    repo: &dyn ProductRepository,
+    owner: Uuid,
+    id: Uuid,
+    name: Option<String>,
+    description: Option<String>,
+) -> Result<(), RepositoryError> {
+    let product = repo.read(id, owner).await?;
+    let new_name = name.unwrap_or(product.name);
+    let new_description = description.unwrap_or(product.description);
+    repo.update(id, owner, &new_name, &new_description).await
+}
+
+async fn await_reply<T>(rx: oneshot::Receiver<Result<T, RepositoryError>>) -> Result<T, RepositoryError> {
+    match rx.await {
+        Ok(result) => result,
+        Err(_) => Err(RepositoryError::Backend(sqlx::Error::PoolClosed)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::async_trait;
+
+    use super::*;
+
+    /// An in-memory `ProductRepository` whose `read`/`update` each yield once,
+    /// opening the same interleaving window a real pooled connection would,
+    /// so a broken actor (one that lets two owner commands run concurrently)
+    /// has a real chance to corrupt a read-then-write.
+    struct InMemoryRepository {
+        rows: Mutex<HashMap<Uuid, Product>>,
+    }
+
+    impl InMemoryRepository {
+        fn new() -> Self {
+            Self {
+                rows: Mutex::new(HashMap::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ProductRepository for InMemoryRepository {
+        async fn create(&self, owner: Uuid, name: &str, description: &str) -> Result<Product, RepositoryError> {
+            let id = Uuid::new_v4();
+            let product = Product {
+                id: id.to_string(),
+                owner: owner.to_string(),
+                name: name.to_owned(),
+                description: description.to_owned(),
+                image_url: None,
+            };
+            self.rows.lock().unwrap().insert(id, product.clone());
+            Ok(product)
+        }
+
+        async fn list(&self, _owner: Uuid, _limit: i64, _offset: i64) -> Result<Vec<Product>, RepositoryError> {
+            Ok(self.rows.lock().unwrap().values().cloned().collect())
+        }
+
+        async fn read(&self, id: Uuid, owner: Uuid) -> Result<Product, RepositoryError> {
+            let product = self
+                .rows
+                .lock()
+                .unwrap()
+                .get(&id)
+                .filter(|p| p.owner == owner.to_string())
+                .cloned()
+                .ok_or(RepositoryError::NotFound)?;
+            tokio::task::yield_now().await;
+            Ok(product)
+        }
+
+        async fn update(&self, id: Uuid, owner: Uuid, name: &str, description: &str) -> Result<(), RepositoryError> {
+            tokio::task::yield_now().await;
+            let mut rows = self.rows.lock().unwrap();
+            let product = rows
+                .get_mut(&id)
+                .filter(|p| p.owner == owner.to_string())
+                .ok_or(RepositoryError::NotFound)?;
+            product.name = name.to_owned();
+            product.description = description.to_owned();
+            Ok(())
+        }
+
+        async fn delete(&self, id: Uuid, owner: Uuid) -> Result<(), RepositoryError> {
+            let mut rows = self.rows.lock().unwrap();
+            if rows.get(&id).is_some_and(|p| p.owner == owner.to_string()) {
+                rows.remove(&id);
+                Ok(())
+            } else {
+                Err(RepositoryError::NotFound)
+            }
+        }
+
+        async fn set_image(&self, id: Uuid, owner: Uuid, image_url: &str) -> Result<(), RepositoryError> {
+            let mut rows = self.rows.lock().unwrap();
+            let product = rows
+                .get_mut(&id)
+                .filter(|p| p.owner == owner.to_string())
+                .ok_or(RepositoryError::NotFound)?;
+            product.image_url = Some(image_url.to_owned());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn concurrent_modifies_to_one_owner_do_not_interleave() {
+        let repo: Arc<dyn ProductRepository> = Arc::new(InMemoryRepository::new());
+        let supervisor = ProductActorSupervisor::new(repo.clone());
+        let owner = Uuid::new_v4();
+        let product = supervisor
+            .create(owner, "widget".to_owned(), "0".to_owned())
+            .await
+            .unwrap();
+        let product_id = Uuid::parse_str(&product.id).unwrap();
+        let supervisor = Arc::new(supervisor);
+
+        let mut handles = Vec::new();
+        for i in 0..30 {
+            let supervisor = supervisor.clone();
+            handles.push(tokio::spawn(async move {
+                supervisor
+                    .modify(owner, product_id, Some(format!("name-{i}")), None)
+                    .await
+                    .unwrap();
+            }));
+            let supervisor = supervisor.clone();
+            handles.push(tokio::spawn(async move {
+                supervisor
+                    .modify(owner, product_id, None, Some(format!("desc-{i}")))
+                    .await
+                    .unwrap();
+            }));
+        }
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        // if the actor let two modifies for the same owner run concurrently,
+        // one command's stale "preserve the other field" read would stomp
+        // the other's write and reset it back to its seeded value
+        let result = supervisor.read(owner, product_id).await.unwrap();
+        assert!(result.name.starts_with("name-"), "name was reset to {:?}", result.name);
+        assert!(
+            result.description.starts_with("desc-"),
+            "description was reset to {:?}",
+            result.description
+        );
+    }
+
+    #[tokio::test]
+    async fn idle_actor_is_respawned_on_next_use() {
+        let repo: Arc<dyn ProductRepository> = Arc::new(InMemoryRepository::new());
+        let supervisor = ProductActorSupervisor::new(repo.clone());
+        let owner = Uuid::new_v4();
+
+        let product = supervisor
+            .create(owner, "widget".to_owned(), "0".to_owned())
+            .await
+            .unwrap();
+        let product_id = Uuid::parse_str(&product.id).unwrap();
+
+        // let the owner's actor task idle out and shut itself down
+        tokio::time::sleep(IDLE_TIMEOUT * 3).await;
+
+        // sender_for() should see the cached sender is closed, spawn a fresh
+        // actor, and this call should succeed exactly as before
+        let product = supervisor.read(owner, product_id).await.unwrap();
+        assert_eq!(product.name, "widget");
+    }
+}
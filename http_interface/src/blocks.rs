@@ -0,0 +1,106 @@
+//! Moderation block/deny list. The `blocks` table is the source of truth; a
+//! `BlockStore` mirrors it in an in-memory `HashSet` so the extractor's block
+//! check never touches SQLite.
+
+use std::{collections::HashSet, sync::RwLock};
+
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockKind {
+    Identifier,
+    Username,
+}
+
+impl BlockKind {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            BlockKind::Identifier => "identifier",
+            BlockKind::Username => "username",
+        }
+    }
+
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "identifier" => Some(BlockKind::Identifier),
+            "username" => Some(BlockKind::Username),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Default, FromRow, Debug, ToSchema)]
+pub struct BlockEntry {
+    pub id: String,
+    pub kind: String,
+    pub subject: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateBlock {
+    pub kind: BlockKind,
+    pub subject: String,
+}
+
+#[derive(Default)]
+pub struct BlockStore {
+    identifiers: RwLock<HashSet<Uuid>>,
+    usernames: RwLock<HashSet<String>>,
+}
+
+impl BlockStore {
+    /// Rehydrates the cache from the `blocks` table at startup.
+    pub async fn rehydrate(pool: &SqlitePool) -> Self {
+        let store = Self::default();
+        let rows: Vec<BlockEntry> = sqlx::query_as("SELECT id, kind, subject FROM blocks")
+            .fetch_all(pool)
+            .await
+            .unwrap_or_default();
+
+        for row in rows {
+            if let Some(kind) = BlockKind::parse(&row.kind) {
+                store.insert(kind, &row.subject);
+            }
+        }
+
+        store
+    }
+
+    pub fn is_identifier_blocked(&self, id: Uuid) -> bool {
+        self.identifiers.read().unwrap().contains(&id)
+    }
+
+    pub fn is_username_blocked(&self, username: &str) -> bool {
+        self.usernames.read().unwrap().contains(username)
+    }
+
+    pub fn insert(&self, kind: BlockKind, subject: &str) {
+        match kind {
+            BlockKind::Identifier => {
+                if let Ok(id) = Uuid::parse_str(subject) {
+                    self.identifiers.write().unwrap().insert(id);
+                }
+            }
+            BlockKind::Username => {
+                self.usernames.write().unwrap().insert(subject.to_owned());
+            }
+        }
+    }
+
+    pub fn remove(&self, kind: BlockKind, subject: &str) {
+        match kind {
+            BlockKind::Identifier => {
+                if let Ok(id) = Uuid::parse_str(subject) {
+                    self.identifiers.write().unwrap().remove(&id);
+                }
+            }
+            BlockKind::Username => {
+                self.usernames.write().unwrap().remove(subject);
+            }
+        }
+    }
+}
@@ -0,0 +1,47 @@
+//! Argon2id password hashing helpers used for user registration and login.
+
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+
+pub const MIN_PASSWORD_LEN: usize = 8;
+
+/// Hashes `password` with Argon2id and a fresh random salt, returning a PHC string.
+pub fn hash(password: &str) -> Result<String, argon2::password_hash::Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = Argon2::default().hash_password(password.as_bytes(), &salt)?;
+    Ok(hash.to_string())
+}
+
+/// Verifies `password` against a previously generated PHC string.
+pub fn verify(password: &str, phc_hash: &str) -> Result<bool, argon2::password_hash::Error> {
+    let parsed_hash = PasswordHash::new(phc_hash)?;
+    Ok(Argon2::default()
+        .verify_password(password.as_bytes(), &parsed_hash)
+        .is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashed_password_verifies() {
+        let hash = hash("correct horse battery staple").unwrap();
+        assert!(verify("correct horse battery staple", &hash).unwrap());
+    }
+
+    #[test]
+    fn wrong_password_does_not_verify() {
+        let hash = hash("correct horse battery staple").unwrap();
+        assert!(!verify("wrong password", &hash).unwrap());
+    }
+
+    #[test]
+    fn distinct_salts_yield_distinct_hashes() {
+        let first = hash("correct horse battery staple").unwrap();
+        let second = hash("correct horse battery staple").unwrap();
+        assert_ne!(first, second);
+    }
+}
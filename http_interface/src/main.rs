@@ -1,57 +1,127 @@
 #![forbid(unsafe_code)]
 
-use std::{
-    collections::HashMap,
-    net::SocketAddr,
-    sync::{Arc, RwLock},
-};
+mod actor;
+mod blocks;
+mod credentials;
+mod openapi;
+mod repository;
+mod storage;
+
+use std::{env, net::SocketAddr, sync::Arc};
 
+use actor::ProductActorSupervisor;
 use axum::{
     async_trait,
-    extract::{FromRef, FromRequestParts, Query, State},
-    http::{request::Parts, StatusCode},
+    body::Bytes,
+    extract::{DefaultBodyLimit, FromRef, FromRequestParts, Multipart, Path, Query, State},
+    http::{header, request::Parts, StatusCode},
     response::IntoResponse,
-    routing::post,
+    routing::{delete, get, post},
     Json, Router,
 };
 use axum_macros::{debug_handler, FromRef};
+use blocks::{BlockEntry, BlockKind, BlockStore, CreateBlock};
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use openapi::ApiDoc;
+use repository::{ProductRepository, SqliteProductRepository};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
+use sqlx::{sqlite::SqlitePoolOptions, FromRow, SqlitePool};
+use storage::{LocalFsStorage, S3Storage, Storage};
+use subtle::ConstantTimeEq;
 use tracing_subscriber::{prelude::__tracing_subscriber_SubscriberExt, util::SubscriberInitExt};
+use utoipa::{IntoParams, OpenApi, ToSchema};
+use utoipa_swagger_ui::SwaggerUi;
 use uuid::Uuid;
 
-#[derive(Deserialize, Serialize, Default, FromRow, Debug)]
+#[derive(Clone, Serialize, Default, FromRow, Debug, ToSchema)]
 struct Product {
+    id: String,
+    owner: String,
+    name: String,
+    description: String,
+    image_url: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+struct CreateProduct {
     name: String,
     description: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct ModifyProduct {
     name: Option<String>,
     description: Option<String>,
 }
 
-#[derive(Serialize, Default)]
+#[derive(Deserialize, IntoParams)]
+struct Pagination {
+    limit: Option<i64>,
+    offset: Option<i64>,
+}
+
+const DEFAULT_PAGE_LIMIT: i64 = 20;
+const MAX_PAGE_LIMIT: i64 = 100;
+
+const MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+const THUMBNAIL_DIM: u32 = 256;
+
+#[derive(Serialize, Default, ToSchema)]
 struct User {
     id: String,
     username: String,
 }
 
-#[derive(Deserialize)]
+#[derive(FromRow)]
+struct UserRecord {
+    id: String,
+    username: String,
+    password_hash: String,
+}
+
+#[derive(Deserialize, ToSchema)]
 struct CreateUser {
     username: String,
+    password: String,
 }
 
-#[derive(Default)]
-struct SharedUser(RwLock<HashMap<Uuid, String>>);
+#[derive(Deserialize, ToSchema)]
+struct LoginRequest {
+    username: String,
+    password: String,
+}
+
+#[derive(Serialize, ToSchema)]
+struct LoginResponse {
+    token: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Claims {
+    sub: String,
+    iat: i64,
+    exp: i64,
+}
 
 struct SharedDB(SqlitePool);
 
+struct JwtConfig {
+    secret: String,
+    expires_in: i64,
+}
+
+struct AdminConfig {
+    api_key: String,
+}
+
 #[derive(Clone, FromRef)]
 struct AppState {
-    user: Arc<SharedUser>,
     pool: Arc<SharedDB>,
+    products: Arc<ProductActorSupervisor>,
+    jwt: Arc<JwtConfig>,
+    blocks: Arc<BlockStore>,
+    images: Arc<dyn Storage>,
+    admin: Arc<AdminConfig>,
 }
 
 #[tokio::main]
@@ -64,26 +134,80 @@ async fn main() {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    // store data in volatile memory
-    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
-    Db::setup(&pool).await;
-    tracing::info!("Sqlite setup complete");
+    let database_url =
+        env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite://rrest.db?mode=rwc".into());
+    let max_connections = env::var("DATABASE_MAX_CONNECTIONS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(5);
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(max_connections)
+        .connect(&database_url)
+        .await
+        .unwrap();
+    sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+    tracing::info!("Sqlite migrations up to date");
+
+    let jwt = JwtConfig {
+        secret: env::var("JWT_SECRET").expect("JWT_SECRET must be set"),
+        expires_in: env::var("JWT_EXPIRES_IN")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(3600),
+    };
+
+    let blocks = BlockStore::rehydrate(&pool).await;
+    tracing::info!("Block list cache rehydrated");
+
+    let admin = AdminConfig {
+        api_key: env::var("ADMIN_API_KEY").expect("ADMIN_API_KEY must be set"),
+    };
+
+    let product_repo: Arc<dyn ProductRepository> = Arc::new(SqliteProductRepository::new(pool.clone()));
+
+    let images: Arc<dyn Storage> = match env::var("IMAGE_STORAGE_BACKEND").as_deref() {
+        Ok("s3") => {
+            let bucket = env::var("IMAGE_STORAGE_BUCKET").expect("IMAGE_STORAGE_BUCKET must be set");
+            Arc::new(S3Storage::new(bucket).await)
+        }
+        _ => {
+            let base_dir = env::var("IMAGE_STORAGE_DIR").unwrap_or_else(|_| "images".into());
+            Arc::new(LocalFsStorage::new(base_dir))
+        }
+    };
 
     // shared state
     let app_state = AppState {
         pool: Arc::new(SharedDB(pool)),
-        user: Arc::new(SharedUser::default()),
+        products: Arc::new(ProductActorSupervisor::new(product_repo)),
+        jwt: Arc::new(jwt),
+        blocks: Arc::new(blocks),
+        images,
+        admin: Arc::new(admin),
     };
     // HTTP interface
     let app = Router::new()
+        .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
         .route("/identifiers", post(create_identifier).get(get_identifier))
+        .route("/login", post(login))
+        .route("/products", post(create_product).get(list_products))
         .route(
-            "/products",
-            post(create_product)
-                .get(get_product)
-                .put(modify_product)
-                .delete(delete_product),
+            "/products/:id",
+            get(get_product).put(modify_product).delete(delete_product),
+        )
+        .merge(
+            Router::new()
+                .route(
+                    "/products/:id/image",
+                    post(upload_product_image).get(get_product_image),
+                )
+                // the default 2 MiB request-body limit would reject uploads
+                // before they ever reach the application's own size check
+                .layer(DefaultBodyLimit::max(MAX_IMAGE_BYTES)),
         )
+        .route("/blocks", post(create_block).get(list_blocks))
+        .route("/blocks/:id", delete(delete_block))
         .with_state(app_state);
 
     // run it with hyper on localhost:3000
@@ -95,67 +219,207 @@ async fn main() {
         .unwrap();
 }
 
+#[utoipa::path(
+    post,
+    path = "/identifiers",
+    request_body = CreateUser,
+    responses(
+        (status = 200, description = "Identifier created", body = String),
+        (status = 400, description = "Password is too short"),
+        (status = 403, description = "Username is blocked"),
+        (status = 409, description = "Username already taken"),
+    )
+)]
 async fn create_identifier(
-    State(user): State<Arc<SharedUser>>,
-    Query(username): Query<CreateUser>,
+    State(pool): State<Arc<SharedDB>>,
+    State(blocks): State<Arc<BlockStore>>,
+    Json(payload): Json<CreateUser>,
 ) -> impl IntoResponse {
+    if payload.password.len() < credentials::MIN_PASSWORD_LEN {
+        return Err((StatusCode::BAD_REQUEST, "password is too short"));
+    }
+
+    if blocks.is_username_blocked(&payload.username) {
+        return Err((StatusCode::FORBIDDEN, "username is blocked"));
+    }
+
+    if Db::find_user_by_username(&payload.username, &pool)
+        .await
+        .is_ok()
+    {
+        return Err((StatusCode::CONFLICT, "username already taken"));
+    }
+
     let id = Uuid::new_v4();
-    let mut usermap = user.0.write().unwrap();
+    let password_hash = credentials::hash(&payload.password)
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "failed to hash password"))?;
 
-    tracing::info!("{} assigned to {}", username.username, id);
-    usermap.insert(id, username.username);
+    Db::create_user(id, &payload.username, &password_hash, &pool)
+        .await
+        .map_err(|status| match status {
+            StatusCode::CONFLICT => (StatusCode::CONFLICT, "username already taken"),
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, "failed to create user"),
+        })?;
 
-    (StatusCode::OK, Json(id.hyphenated().to_string()))
+    tracing::info!("{} assigned to {}", payload.username, id);
+    Ok((StatusCode::OK, Json(id.hyphenated().to_string())))
 }
 
+#[utoipa::path(
+    post,
+    path = "/login",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Session token issued", body = LoginResponse),
+        (status = 401, description = "Invalid username or password"),
+    )
+)]
+async fn login(
+    State(pool): State<Arc<SharedDB>>,
+    State(jwt): State<Arc<JwtConfig>>,
+    Json(payload): Json<LoginRequest>,
+) -> impl IntoResponse {
+    let Ok(user) = Db::find_user_by_username(&payload.username, &pool).await else {
+        return Err((StatusCode::UNAUTHORIZED, "invalid username or password"));
+    };
+
+    let verified = credentials::verify(&payload.password, &user.password_hash)
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "failed to verify password"))?;
+    if !verified {
+        return Err((StatusCode::UNAUTHORIZED, "invalid username or password"));
+    }
+
+    let Ok(id) = Uuid::parse_str(&user.id) else {
+        return Err((StatusCode::INTERNAL_SERVER_ERROR, "corrupt identifier"));
+    };
+
+    let token = mint_token(id, &jwt).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "failed to mint session token",
+        )
+    })?;
+
+    Ok((StatusCode::OK, Json(LoginResponse { token })))
+}
+
+fn mint_token(id: Uuid, jwt: &JwtConfig) -> Result<String, jsonwebtoken::errors::Error> {
+    let now = chrono::Utc::now().timestamp();
+    let claims = Claims {
+        sub: id.to_string(),
+        iat: now,
+        exp: now + jwt.expires_in,
+    };
+
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(jwt.secret.as_bytes()),
+    )
+}
+
+#[utoipa::path(
+    post,
+    path = "/products",
+    request_body = CreateProduct,
+    params(("Authorization" = String, Header, description = "Bearer session token")),
+    responses(
+        (status = 201, description = "Product created", body = Product),
+        (status = 401, description = "Missing or invalid session token"),
+        (status = 403, description = "Identifier is blocked or unknown"),
+    )
+)]
 async fn create_product(
     id: RequiredUserId,
-    State(pool): State<Arc<SharedDB>>,
-    Json(payload): Json<Product>,
+    State(products): State<Arc<ProductActorSupervisor>>,
+    Json(payload): Json<CreateProduct>,
 ) -> impl IntoResponse {
-    if (Db::read_product(id.0, &pool).await).is_err() {
-        let res = Db::save_product(id.0, &payload.name, &payload.description, pool).await;
-
-        match res {
-            Ok(_) => {
-                tracing::info!("Inserted product for {}", id.0);
-                (
-                    StatusCode::CREATED,
-                    Json(Product {
-                        name: payload.name,
-                        description: payload.description,
-                    }),
-                )
-            }
-            Err(err) => (err, Json(Product::default())),
+    match products.create(id.0, payload.name, payload.description).await {
+        Ok(product) => {
+            tracing::info!("Inserted product {} for {}", product.id, id.0);
+            (StatusCode::CREATED, Json(product))
         }
-    } else {
-        tracing::info!("{} already owns a product", id.0);
-        (StatusCode::CONFLICT, Json(Product::default()))
+        Err(err) => (err.into(), Json(Product::default())),
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/products",
+    params(
+        Pagination,
+        ("Authorization" = String, Header, description = "Bearer session token"),
+    ),
+    responses(
+        (status = 200, description = "The caller's products", body = [Product]),
+        (status = 401, description = "Missing or invalid session token"),
+        (status = 403, description = "Identifier is blocked or unknown"),
+    )
+)]
+async fn list_products(
+    id: RequiredUserId,
+    State(products): State<Arc<ProductActorSupervisor>>,
+    Query(page): Query<Pagination>,
+) -> impl IntoResponse {
+    let limit = page.limit.unwrap_or(DEFAULT_PAGE_LIMIT).clamp(1, MAX_PAGE_LIMIT);
+    let offset = page.offset.unwrap_or(0).max(0);
+
+    match products.list(id.0, limit, offset).await {
+        Ok(list) => (StatusCode::OK, Json(list)),
+        Err(err) => (err.into(), Json(Vec::new())),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/identifiers",
+    params(("Authorization" = String, Header, description = "Bearer session token")),
+    responses(
+        (status = 302, description = "Identifier found", body = User),
+        (status = 401, description = "Missing or invalid session token"),
+        (status = 403, description = "Identifier is blocked or unknown"),
+        (status = 404, description = "Identifier not found"),
+    )
+)]
 async fn get_identifier(
     id: RequiredUserId,
-    State(user): State<Arc<SharedUser>>,
+    State(pool): State<Arc<SharedDB>>,
 ) -> impl IntoResponse {
-    let list = user.0.read().unwrap();
-    let result = list.get_key_value(&id.0).unwrap();
-    tracing::info!("Information provided about: {}", result.1);
+    let Ok(user) = Db::find_user_by_id(id.0, &pool).await else {
+        return (StatusCode::NOT_FOUND, Json(User::default()));
+    };
+    tracing::info!("Information provided about: {}", user.username);
 
     (
         StatusCode::FOUND,
         Json(User {
-            id: result.0.to_string(),
-            username: result.1.to_owned(),
+            id: user.id,
+            username: user.username,
         }),
     )
 }
 
+#[utoipa::path(
+    get,
+    path = "/products/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Product identifier"),
+        ("Authorization" = String, Header, description = "Bearer session token"),
+    ),
+    responses(
+        (status = 302, description = "Product found", body = Product),
+        (status = 401, description = "Missing or invalid session token"),
+        (status = 403, description = "Identifier is blocked or unknown"),
+        (status = 404, description = "Product not found"),
+    )
+)]
 #[debug_handler(state = AppState)]
-async fn get_product(id: RequiredUserId, State(pool): State<Arc<SharedDB>>) -> impl IntoResponse {
-    let product = Db::read_product(id.0, &pool).await;
-    let Ok(product) = product else {
+async fn get_product(
+    id: RequiredUserId,
+    Path(product_id): Path<Uuid>,
+    State(products): State<Arc<ProductActorSupervisor>>,
+) -> impl IntoResponse {
+    let Ok(product) = products.read(id.0, product_id).await else {
         return (StatusCode::NOT_FOUND, Json(Product::default()))
     };
 
@@ -163,37 +427,268 @@ async fn get_product(id: RequiredUserId, State(pool): State<Arc<SharedDB>>) -> i
     (StatusCode::FOUND, Json(product))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/products/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Product identifier"),
+        ("Authorization" = String, Header, description = "Bearer session token"),
+    ),
+    responses(
+        (status = 204, description = "Product removed"),
+        (status = 401, description = "Missing or invalid session token"),
+        (status = 403, description = "Identifier is blocked or unknown"),
+        (status = 404, description = "Product not found"),
+    )
+)]
 #[debug_handler(state = AppState)]
 async fn delete_product(
     id: RequiredUserId,
-    State(pool): State<Arc<SharedDB>>,
+    Path(product_id): Path<Uuid>,
+    State(products): State<Arc<ProductActorSupervisor>>,
 ) -> impl IntoResponse {
-    match Db::delete_product(id.0, &pool).await {
-        Ok(_) => {
-            tracing::info!("{} removed his product", id.0);
+    match products.delete(id.0, product_id).await {
+        Ok(()) => {
+            tracing::info!("{} removed product {}", id.0, product_id);
             StatusCode::NO_CONTENT
         }
-        Err(err) => err,
+        Err(err) => err.into(),
     }
 }
 
+#[utoipa::path(
+    put,
+    path = "/products/{id}",
+    request_body = ModifyProduct,
+    params(
+        ("id" = Uuid, Path, description = "Product identifier"),
+        ("Authorization" = String, Header, description = "Bearer session token"),
+    ),
+    responses(
+        (status = 204, description = "Product updated"),
+        (status = 401, description = "Missing or invalid session token"),
+        (status = 403, description = "Identifier is blocked or unknown"),
+        (status = 404, description = "Product not found"),
+    )
+)]
 async fn modify_product(
     id: RequiredUserId,
-    State(pool): State<Arc<SharedDB>>,
+    Path(product_id): Path<Uuid>,
+    State(products): State<Arc<ProductActorSupervisor>>,
     Json(payload): Json<ModifyProduct>,
 ) -> impl IntoResponse {
-    match Db::read_product(id.0, &pool).await {
-        Ok(product) => {
-            let new_name = payload.name.unwrap_or(product.name);
-            let new_description = payload.description.unwrap_or(product.description);
+    match products
+        .modify(id.0, product_id, payload.name, payload.description)
+        .await
+    {
+        Ok(()) => {
+            tracing::info!("{} updates product {}", id.0, product_id);
+            StatusCode::NO_CONTENT
+        }
+        Err(err) => err.into(),
+    }
+}
 
-            match Db::update_product(id.0, &new_name, &new_description, &pool).await {
-                Ok(_) => {
-                    tracing::info!("{} updates his product", id.0);
-                    StatusCode::NO_CONTENT
-                }
-                Err(err) => err,
+#[utoipa::path(
+    post,
+    path = "/products/{id}/image",
+    params(
+        ("id" = Uuid, Path, description = "Product identifier"),
+        ("Authorization" = String, Header, description = "Bearer session token"),
+    ),
+    responses(
+        (status = 204, description = "Image stored"),
+        (status = 400, description = "Upload is missing, too large, or not a decodable image"),
+        (status = 401, description = "Missing or invalid session token"),
+        (status = 403, description = "Identifier is blocked or unknown"),
+        (status = 404, description = "Product not found"),
+    )
+)]
+async fn upload_product_image(
+    id: RequiredUserId,
+    Path(product_id): Path<Uuid>,
+    State(products): State<Arc<ProductActorSupervisor>>,
+    State(images): State<Arc<dyn Storage>>,
+    mut multipart: Multipart,
+) -> impl IntoResponse {
+    if products.read(id.0, product_id).await.is_err() {
+        return Err((StatusCode::NOT_FOUND, "product not found"));
+    }
+
+    let Some(bytes) = image_field(&mut multipart).await? else {
+        return Err((StatusCode::BAD_REQUEST, "missing image field"));
+    };
+
+    if bytes.len() > MAX_IMAGE_BYTES {
+        return Err((StatusCode::BAD_REQUEST, "image exceeds size limit"));
+    }
+
+    let thumbnail =
+        encode_thumbnail(&bytes).map_err(|_| (StatusCode::BAD_REQUEST, "upload is not a valid image"))?;
+
+    let key = storage::object_key(id.0, product_id);
+    images
+        .put(&key, thumbnail, "image/png")
+        .await
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "failed to store image"))?;
+
+    products
+        .set_image(id.0, product_id, key)
+        .await
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "failed to save image reference"))?;
+
+    tracing::info!("{} uploaded an image for product {}", id.0, product_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Scans the multipart body for the field named `"image"`, rather than
+/// assuming it's whichever part the client happened to send first.
+async fn image_field(multipart: &mut Multipart) -> Result<Option<Bytes>, (StatusCode, &'static str)> {
+    while let Ok(Some(field)) = multipart.next_field().await {
+        if field.name() != Some("image") {
+            continue;
+        }
+
+        let bytes = field
+            .bytes()
+            .await
+            .map_err(|_| (StatusCode::BAD_REQUEST, "failed to read upload"))?;
+        return Ok(Some(bytes));
+    }
+
+    Ok(None)
+}
+
+/// Validates that `bytes` decodes as an image and re-encodes it as a
+/// [`THUMBNAIL_DIM`]-capped PNG, both to bound storage size and to ensure the
+/// bytes we persist are well-formed regardless of the upload's original format.
+fn encode_thumbnail(bytes: &[u8]) -> image::ImageResult<Vec<u8>> {
+    let thumbnail = image::load_from_memory(bytes)?.thumbnail(THUMBNAIL_DIM, THUMBNAIL_DIM);
+
+    let mut encoded = Vec::new();
+    thumbnail.write_to(&mut std::io::Cursor::new(&mut encoded), image::ImageFormat::Png)?;
+    Ok(encoded)
+}
+
+#[utoipa::path(
+    get,
+    path = "/products/{id}/image",
+    params(
+        ("id" = Uuid, Path, description = "Product identifier"),
+        ("Authorization" = String, Header, description = "Bearer session token"),
+    ),
+    responses(
+        (status = 200, description = "Image bytes", content_type = "image/png"),
+        (status = 401, description = "Missing or invalid session token"),
+        (status = 403, description = "Identifier is blocked or unknown"),
+        (status = 404, description = "Product or image not found"),
+    )
+)]
+async fn get_product_image(
+    id: RequiredUserId,
+    Path(product_id): Path<Uuid>,
+    State(products): State<Arc<ProductActorSupervisor>>,
+    State(images): State<Arc<dyn Storage>>,
+) -> impl IntoResponse {
+    let Ok(product) = products.read(id.0, product_id).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let Some(key) = product.image_url else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    match images.get(&key).await {
+        Ok(image) => ([(header::CONTENT_TYPE, image.content_type)], image.bytes).into_response(),
+        Err(err) => StatusCode::from(err).into_response(),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/blocks",
+    request_body = CreateBlock,
+    params(("X-Admin-Key" = String, Header, description = "Admin API key")),
+    responses(
+        (status = 201, description = "Block recorded", body = BlockEntry),
+        (status = 400, description = "Identifier subject is not a valid UUID"),
+        (status = 401, description = "Missing or invalid admin key"),
+        (status = 500, description = "Failed to persist block"),
+    )
+)]
+async fn create_block(
+    _admin: RequireAdmin,
+    State(pool): State<Arc<SharedDB>>,
+    State(blocks): State<Arc<BlockStore>>,
+    Json(payload): Json<CreateBlock>,
+) -> impl IntoResponse {
+    if payload.kind == BlockKind::Identifier && Uuid::parse_str(&payload.subject).is_err() {
+        return Err((StatusCode::BAD_REQUEST, "identifier subject must be a valid UUID"));
+    }
+
+    let id = Uuid::new_v4();
+    match Db::create_block(id, payload.kind, &payload.subject, &pool).await {
+        Ok(()) => {
+            blocks.insert(payload.kind, &payload.subject);
+            tracing::info!("Blocked {} {}", payload.kind.as_str(), payload.subject);
+            Ok((
+                StatusCode::CREATED,
+                Json(BlockEntry {
+                    id: id.to_string(),
+                    kind: payload.kind.as_str().to_owned(),
+                    subject: payload.subject,
+                }),
+            ))
+        }
+        Err(err) => Err((err, "failed to persist block")),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/blocks",
+    params(("X-Admin-Key" = String, Header, description = "Admin API key")),
+    responses(
+        (status = 200, description = "All recorded blocks", body = [BlockEntry]),
+        (status = 401, description = "Missing or invalid admin key"),
+    )
+)]
+async fn list_blocks(_admin: RequireAdmin, State(pool): State<Arc<SharedDB>>) -> impl IntoResponse {
+    match Db::list_blocks(&pool).await {
+        Ok(entries) => (StatusCode::OK, Json(entries)),
+        Err(err) => (err, Json(Vec::new())),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/blocks/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Block entry identifier"),
+        ("X-Admin-Key" = String, Header, description = "Admin API key"),
+    ),
+    responses(
+        (status = 204, description = "Block removed"),
+        (status = 401, description = "Missing or invalid admin key"),
+        (status = 404, description = "Block entry not found"),
+    )
+)]
+async fn delete_block(
+    _admin: RequireAdmin,
+    Path(id): Path<Uuid>,
+    State(pool): State<Arc<SharedDB>>,
+    State(blocks): State<Arc<BlockStore>>,
+) -> impl IntoResponse {
+    let Ok(entry) = Db::find_block(id, &pool).await else {
+        return StatusCode::NOT_FOUND;
+    };
+
+    match Db::delete_block(id, &pool).await {
+        Ok(()) => {
+            if let Some(kind) = BlockKind::parse(&entry.kind) {
+                blocks.remove(kind, &entry.subject);
             }
+            tracing::info!("Unblocked {} {}", entry.kind, entry.subject);
+            StatusCode::NO_CONTENT
         }
         Err(err) => err,
     }
@@ -204,128 +699,204 @@ struct RequiredUserId(Uuid);
 #[async_trait]
 impl<S> FromRequestParts<S> for RequiredUserId
 where
-    Arc<SharedUser>: FromRef<S>,
+    Arc<SharedDB>: FromRef<S>,
+    Arc<JwtConfig>: FromRef<S>,
+    Arc<BlockStore>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let pool = Arc::<SharedDB>::from_ref(state);
+        let jwt = Arc::<JwtConfig>::from_ref(state);
+        let blocks = Arc::<BlockStore>::from_ref(state);
+
+        let token = bearer_token(parts).ok_or((StatusCode::UNAUTHORIZED, "missing session token"))?;
+
+        verify_token(token, &jwt, &pool, &blocks).await
+    }
+}
+
+/// Gates the moderation routes behind a shared admin key, so the block list
+/// can't be read or edited by the ordinary users it moderates.
+struct RequireAdmin;
+
+#[async_trait]
+impl<S> FromRequestParts<S> for RequireAdmin
+where
+    Arc<AdminConfig>: FromRef<S>,
     S: Send + Sync,
 {
     type Rejection = (StatusCode, &'static str);
 
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
-        let user = Arc::<SharedUser>::from_ref(state);
+        let admin = Arc::<AdminConfig>::from_ref(state);
 
-        let id = parts
+        let provided = parts
             .headers
-            .get("uuid")
-            .and_then(|id| id.to_str().ok())
-            .ok_or((StatusCode::FORBIDDEN, "Please pass your identifier"))?;
+            .get("X-Admin-Key")
+            .and_then(|v| v.to_str().ok());
+
+        match provided {
+            // constant-time so a malicious client can't learn the key by
+            // timing how far a guess's prefix matches
+            Some(key) if bool::from(key.as_bytes().ct_eq(admin.api_key.as_bytes())) => {
+                Ok(RequireAdmin)
+            }
+            _ => Err((StatusCode::UNAUTHORIZED, "missing or invalid admin key")),
+        }
+    }
+}
 
-        verify_uuid(id, user).await
+// pulls the token from `Authorization: Bearer <token>`, falling back to a `token` cookie
+// whenever the header is missing or doesn't carry a Bearer-scheme value
+fn bearer_token(parts: &Parts) -> Option<&str> {
+    let bearer = parts
+        .headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    if let Some(token) = bearer {
+        return Some(token);
     }
+
+    parts
+        .headers
+        .get(header::COOKIE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|cookies| cookies.split(';').find_map(|c| c.trim().strip_prefix("token=")))
 }
 
-async fn verify_uuid(
-    uuid: &str,
-    user: Arc<SharedUser>,
+async fn verify_token(
+    token: &str,
+    jwt: &JwtConfig,
+    pool: &Arc<SharedDB>,
+    blocks: &BlockStore,
 ) -> Result<RequiredUserId, (StatusCode, &'static str)> {
-    let Ok(uuid) = Uuid::parse_str(uuid) else {
-        return Err((StatusCode::FORBIDDEN,"Invalid identifier"))
+    let claims = decode::<Claims>(
+        token,
+        &DecodingKey::from_secret(jwt.secret.as_bytes()),
+        &Validation::default(),
+    )
+    .map_err(|_| (StatusCode::UNAUTHORIZED, "token invalid or expired"))?
+    .claims;
+
+    let Ok(uuid) = Uuid::parse_str(&claims.sub) else {
+        return Err((StatusCode::UNAUTHORIZED, "token invalid or expired"));
     };
 
-    let usermap = user.0.read().unwrap();
-    usermap
-        .get(&uuid)
-        .ok_or((StatusCode::FORBIDDEN, "Invalid identifier!"))
+    verify_uuid(uuid, pool, blocks).await
+}
+
+async fn verify_uuid(
+    uuid: Uuid,
+    pool: &Arc<SharedDB>,
+    blocks: &BlockStore,
+) -> Result<RequiredUserId, (StatusCode, &'static str)> {
+    if blocks.is_identifier_blocked(uuid) {
+        return Err((StatusCode::FORBIDDEN, "identifier is blocked"));
+    }
+
+    Db::find_user_by_id(uuid, pool)
+        .await
+        .map_err(|_| (StatusCode::FORBIDDEN, "Invalid identifier!"))
         .map(|_| RequiredUserId(uuid))
 }
 
 pub struct Db;
 
 impl Db {
-    async fn save_product(
-        uuid: Uuid,
-        name: &str,
-        description: &str,
-        pool: Arc<SharedDB>,
+    async fn create_user(
+        id: Uuid,
+        username: &str,
+        password_hash: &str,
+        pool: &SharedDB,
     ) -> Result<(), StatusCode> {
-        let result = sqlx::query(
-            "
-            INSERT INTO 
-            product (owner, name, description)
-            VALUES (?1, ?2, ?3)
-                ",
-        )
-        .bind(uuid.to_string())
-        .bind(name)
-        .bind(description)
-        .execute(&pool.0)
-        .await
-        .unwrap();
+        sqlx::query("INSERT INTO users (id, username, password_hash) VALUES (?1, ?2, ?3)")
+            .bind(id.to_string())
+            .bind(username)
+            .bind(password_hash)
+            .execute(&pool.0)
+            .await
+            .map_err(|err| {
+                // a concurrent registration can win the race against the
+                // earlier find_user_by_username check; the UNIQUE constraint
+                // on users.username is the real guard, so surface its
+                // violation as a conflict rather than a server error
+                if err.as_database_error().is_some_and(|db_err| db_err.is_unique_violation()) {
+                    StatusCode::CONFLICT
+                } else {
+                    StatusCode::INTERNAL_SERVER_ERROR
+                }
+            })?;
 
-        let true = result.rows_affected() == 1 else {
-        // this should never happen; INSERT error
-        return Err(StatusCode::INTERNAL_SERVER_ERROR);
-    };
         Ok(())
     }
 
-    async fn read_product(uuid: Uuid, pool: &Arc<SharedDB>) -> Result<Product, StatusCode> {
-        let product =
-            sqlx::query_as::<_, Product>("SELECT name, description FROM product WHERE owner = ?1")
-                .bind(uuid.to_string())
-                .fetch_one(&pool.0)
-                .await;
+    async fn find_user_by_username(username: &str, pool: &SharedDB) -> Result<UserRecord, StatusCode> {
+        sqlx::query_as::<_, UserRecord>(
+            "SELECT id, username, password_hash FROM users WHERE username = ?1",
+        )
+        .bind(username)
+        .fetch_one(&pool.0)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)
+    }
 
-        let Ok(product) = product else {
-        return Err(StatusCode::NOT_FOUND)
-    };
-        Ok(product)
+    async fn find_user_by_id(id: Uuid, pool: &SharedDB) -> Result<UserRecord, StatusCode> {
+        sqlx::query_as::<_, UserRecord>(
+            "SELECT id, username, password_hash FROM users WHERE id = ?1",
+        )
+        .bind(id.to_string())
+        .fetch_one(&pool.0)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)
     }
 
-    async fn update_product(
-        uuid: Uuid,
-        name: &str,
-        description: &str,
-        pool: &Arc<SharedDB>,
+    async fn create_block(
+        id: Uuid,
+        kind: BlockKind,
+        subject: &str,
+        pool: &SharedDB,
     ) -> Result<(), StatusCode> {
-        let result = sqlx::query("UPDATE product SET name = ?1, description = ?2 WHERE owner = ?3")
-            .bind(name)
-            .bind(description)
-            .bind(uuid.to_string())
+        sqlx::query("INSERT INTO blocks (id, kind, subject) VALUES (?1, ?2, ?3)")
+            .bind(id.to_string())
+            .bind(kind.as_str())
+            .bind(subject)
             .execute(&pool.0)
             .await
-            .unwrap();
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-        let true = result.rows_affected() == 1 else {
-        // this should never happen; INSERT error
-        return Err(StatusCode::INTERNAL_SERVER_ERROR);
-    };
         Ok(())
     }
 
-    async fn delete_product(uuid: Uuid, pool: &Arc<SharedDB>) -> Result<(), StatusCode> {
-        let result = sqlx::query("DELETE FROM product WHERE owner = ?1")
-            .bind(uuid.to_string())
-            .execute(&pool.0)
+    async fn find_block(id: Uuid, pool: &SharedDB) -> Result<BlockEntry, StatusCode> {
+        sqlx::query_as("SELECT id, kind, subject FROM blocks WHERE id = ?1")
+            .bind(id.to_string())
+            .fetch_one(&pool.0)
             .await
-            .unwrap();
+            .map_err(|_| StatusCode::NOT_FOUND)
+    }
 
-        let true = result.rows_affected() == 1 else {
-        tracing::info!("{} does not own a product", uuid);
-        return Err(StatusCode::NOT_FOUND);
-        };
-        Ok(())
+    async fn list_blocks(pool: &SharedDB) -> Result<Vec<BlockEntry>, StatusCode> {
+        sqlx::query_as("SELECT id, kind, subject FROM blocks")
+            .fetch_all(&pool.0)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
     }
 
-    async fn setup(pool: &SqlitePool) {
-        sqlx::query(
-            "
-        CREATE TABLE product (
-        owner text,
-        name text,
-        description text
-            )",
-        )
-        .execute(pool)
-        .await
-        .unwrap();
+    async fn delete_block(id: Uuid, pool: &SharedDB) -> Result<(), StatusCode> {
+        let result = sqlx::query("DELETE FROM blocks WHERE id = ?1")
+            .bind(id.to_string())
+            .execute(&pool.0)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        if result.rows_affected() == 1 {
+            Ok(())
+        } else {
+            Err(StatusCode::NOT_FOUND)
+        }
     }
 }
@@ -0,0 +1,44 @@
+//! Generated OpenAPI document for the `/identifiers`, `/products`, and
+//! `/blocks` routes, served via Swagger UI at `/swagger-ui`.
+
+use utoipa::OpenApi;
+
+use crate::{
+    blocks::{BlockEntry, BlockKind, CreateBlock},
+    create_block, create_identifier, create_product, delete_block, delete_product,
+    get_identifier, get_product, get_product_image, list_blocks, list_products, login,
+    modify_product, upload_product_image, CreateProduct, CreateUser, LoginRequest, LoginResponse,
+    ModifyProduct, Product, User,
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        create_identifier,
+        get_identifier,
+        login,
+        create_product,
+        list_products,
+        get_product,
+        modify_product,
+        delete_product,
+        upload_product_image,
+        get_product_image,
+        create_block,
+        list_blocks,
+        delete_block,
+    ),
+    components(schemas(
+        User,
+        CreateUser,
+        LoginRequest,
+        LoginResponse,
+        Product,
+        CreateProduct,
+        ModifyProduct,
+        BlockEntry,
+        CreateBlock,
+        BlockKind,
+    ))
+)]
+pub struct ApiDoc;
@@ -0,0 +1,157 @@
+//! Repository abstraction over product storage, so handlers depend on a trait
+//! object rather than hard-coded SQLite calls.
+
+use axum::{async_trait, http::StatusCode};
+use sqlx::{sqlite::SqliteRow, FromRow, SqlitePool};
+use uuid::Uuid;
+
+use crate::Product;
+
+#[derive(Debug)]
+pub enum RepositoryError {
+    NotFound,
+    Conflict,
+    Backend(sqlx::Error),
+}
+
+impl From<RepositoryError> for StatusCode {
+    fn from(err: RepositoryError) -> Self {
+        match err {
+            RepositoryError::NotFound => StatusCode::NOT_FOUND,
+            RepositoryError::Conflict => StatusCode::CONFLICT,
+            RepositoryError::Backend(err) => {
+                tracing::error!("repository backend error: {err}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+}
+
+#[async_trait]
+pub trait ProductRepository: Send + Sync {
+    async fn create(&self, owner: Uuid, name: &str, description: &str) -> Result<Product, RepositoryError>;
+    async fn list(&self, owner: Uuid, limit: i64, offset: i64) -> Result<Vec<Product>, RepositoryError>;
+    async fn read(&self, id: Uuid, owner: Uuid) -> Result<Product, RepositoryError>;
+    async fn update(&self, id: Uuid, owner: Uuid, name: &str, description: &str) -> Result<(), RepositoryError>;
+    async fn delete(&self, id: Uuid, owner: Uuid) -> Result<(), RepositoryError>;
+    async fn set_image(&self, id: Uuid, owner: Uuid, image_url: &str) -> Result<(), RepositoryError>;
+}
+
+pub struct SqliteProductRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteProductRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ProductRepository for SqliteProductRepository {
+    async fn create(&self, owner: Uuid, name: &str, description: &str) -> Result<Product, RepositoryError> {
+        let id = Uuid::new_v4();
+
+        sqlx::query("INSERT INTO product (id, owner, name, description) VALUES (?1, ?2, ?3, ?4)")
+            .bind(id.to_string())
+            .bind(owner.to_string())
+            .bind(name)
+            .bind(description)
+            .execute(&self.pool)
+            .await
+            .map_err(RepositoryError::Backend)?;
+
+        Ok(Product {
+            id: id.to_string(),
+            owner: owner.to_string(),
+            name: name.to_owned(),
+            description: description.to_owned(),
+            image_url: None,
+        })
+    }
+
+    async fn list(&self, owner: Uuid, limit: i64, offset: i64) -> Result<Vec<Product>, RepositoryError> {
+        sqlx::query_as(
+            "SELECT id, owner, name, description, image_url FROM product WHERE owner = ?1 ORDER BY rowid LIMIT ?2 OFFSET ?3",
+        )
+        .bind(owner.to_string())
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Backend)
+    }
+
+    async fn read(&self, id: Uuid, owner: Uuid) -> Result<Product, RepositoryError> {
+        fetch_one(
+            sqlx::query_as(
+                "SELECT id, owner, name, description, image_url FROM product WHERE id = ?1 AND owner = ?2",
+            )
+            .bind(id.to_string())
+            .bind(owner.to_string()),
+            &self.pool,
+        )
+        .await
+    }
+
+    async fn update(&self, id: Uuid, owner: Uuid, name: &str, description: &str) -> Result<(), RepositoryError> {
+        let result = sqlx::query(
+            "UPDATE product SET name = ?1, description = ?2 WHERE id = ?3 AND owner = ?4",
+        )
+        .bind(name)
+        .bind(description)
+        .bind(id.to_string())
+        .bind(owner.to_string())
+        .execute(&self.pool)
+        .await
+        .map_err(RepositoryError::Backend)?;
+
+        rows_affected_one(result.rows_affected())
+    }
+
+    async fn delete(&self, id: Uuid, owner: Uuid) -> Result<(), RepositoryError> {
+        let result = sqlx::query("DELETE FROM product WHERE id = ?1 AND owner = ?2")
+            .bind(id.to_string())
+            .bind(owner.to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(RepositoryError::Backend)?;
+
+        rows_affected_one(result.rows_affected())
+    }
+
+    async fn set_image(&self, id: Uuid, owner: Uuid, image_url: &str) -> Result<(), RepositoryError> {
+        let result = sqlx::query("UPDATE product SET image_url = ?1 WHERE id = ?2 AND owner = ?3")
+            .bind(image_url)
+            .bind(id.to_string())
+            .bind(owner.to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(RepositoryError::Backend)?;
+
+        rows_affected_one(result.rows_affected())
+    }
+}
+
+/// Centralizes row-not-found handling for the single-row repository queries.
+async fn fetch_one<T>(
+    query: sqlx::query::QueryAs<'_, sqlx::Sqlite, T, sqlx::sqlite::SqliteArguments<'_>>,
+    pool: &SqlitePool,
+) -> Result<T, RepositoryError>
+where
+    T: for<'r> FromRow<'r, SqliteRow> + Send + Unpin,
+{
+    query.fetch_one(pool).await.map_err(|err| match err {
+        sqlx::Error::RowNotFound => RepositoryError::NotFound,
+        other => RepositoryError::Backend(other),
+    })
+}
+
+fn rows_affected_one(rows_affected: u64) -> Result<(), RepositoryError> {
+    match rows_affected {
+        1 => Ok(()),
+        0 => Err(RepositoryError::NotFound),
+        // more than one row touched by an id+owner-scoped query should never happen
+        _ => Err(RepositoryError::Conflict),
+    }
+}
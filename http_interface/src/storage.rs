@@ -0,0 +1,147 @@
+//! Blob storage for product images. Handlers store and fetch image bytes
+//! through the [`Storage`] trait so the backend (local filesystem or an
+//! S3-compatible bucket) is a deployment detail picked once in `main`.
+
+use std::path::PathBuf;
+
+use axum::{async_trait, http::StatusCode};
+use uuid::Uuid;
+
+#[derive(Debug)]
+pub enum StorageError {
+    NotFound,
+    Backend(String),
+}
+
+impl From<StorageError> for StatusCode {
+    fn from(err: StorageError) -> Self {
+        match err {
+            StorageError::NotFound => StatusCode::NOT_FOUND,
+            StorageError::Backend(err) => {
+                tracing::error!("storage backend error: {err}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+}
+
+/// A stored image's bytes alongside the content type it was saved with.
+pub struct StoredImage {
+    pub bytes: Vec<u8>,
+    pub content_type: String,
+}
+
+#[async_trait]
+pub trait Storage: Send + Sync {
+    /// Stores `bytes` under `key` and returns the key (or URL) to persist as
+    /// the product's `image_url`.
+    async fn put(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> Result<String, StorageError>;
+
+    async fn get(&self, key: &str) -> Result<StoredImage, StorageError>;
+}
+
+/// Builds the object key a product's image is stored under. Scoping by owner
+/// keeps one owner's objects out of another's directory/prefix.
+pub fn object_key(owner: Uuid, product_id: Uuid) -> String {
+    format!("{owner}/{product_id}.png")
+}
+
+pub struct LocalFsStorage {
+    base_dir: PathBuf,
+}
+
+impl LocalFsStorage {
+    pub fn new(base_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            base_dir: base_dir.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Storage for LocalFsStorage {
+    async fn put(&self, key: &str, bytes: Vec<u8>, _content_type: &str) -> Result<String, StorageError> {
+        let path = self.base_dir.join(key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|err| StorageError::Backend(err.to_string()))?;
+        }
+
+        tokio::fs::write(&path, bytes)
+            .await
+            .map_err(|err| StorageError::Backend(err.to_string()))?;
+
+        Ok(key.to_owned())
+    }
+
+    async fn get(&self, key: &str) -> Result<StoredImage, StorageError> {
+        let path = self.base_dir.join(key);
+        let bytes = tokio::fs::read(&path).await.map_err(|err| match err.kind() {
+            std::io::ErrorKind::NotFound => StorageError::NotFound,
+            _ => StorageError::Backend(err.to_string()),
+        })?;
+
+        Ok(StoredImage {
+            bytes,
+            content_type: "image/png".to_owned(),
+        })
+    }
+}
+
+pub struct S3Storage {
+    client: aws_sdk_s3::Client,
+    bucket: String,
+}
+
+impl S3Storage {
+    pub async fn new(bucket: String) -> Self {
+        let config = aws_config::load_from_env().await;
+        Self {
+            client: aws_sdk_s3::Client::new(&config),
+            bucket,
+        }
+    }
+}
+
+#[async_trait]
+impl Storage for S3Storage {
+    async fn put(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> Result<String, StorageError> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .body(bytes.into())
+            .content_type(content_type)
+            .send()
+            .await
+            .map_err(|err| StorageError::Backend(err.to_string()))?;
+
+        Ok(key.to_owned())
+    }
+
+    async fn get(&self, key: &str) -> Result<StoredImage, StorageError> {
+        let output = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|err| StorageError::Backend(err.to_string()))?;
+
+        let content_type = output
+            .content_type()
+            .unwrap_or("image/png")
+            .to_owned();
+        let bytes = output
+            .body
+            .collect()
+            .await
+            .map_err(|err| StorageError::Backend(err.to_string()))?
+            .into_bytes()
+            .to_vec();
+
+        Ok(StoredImage { bytes, content_type })
+    }
+}